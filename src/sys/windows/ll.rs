@@ -1,24 +1,340 @@
 //! A thread safe linked list that allows for removal
 
-use std::{mem, ptr};
+use std::iter::Rev;
+use std::marker::{PhantomData, PhantomPinned};
+use std::mem;
+use std::pin::Pin;
+use std::ptr::{self, NonNull};
+
+/// Intrusive link fields embedded inside a caller-owned node.
+///
+/// A `Links<T>` is meant to live as a field inside `T` itself. Because the
+/// list only ever touches the `Links` field rather than allocating its own
+/// node, a single value can embed one `Links<T>` per list it needs to be a
+/// member of and be linked into all of them at once with no extra
+/// allocation.
+///
+/// `Links` is `!Unpin`: once a node is linked, the list holds raw pointers
+/// into it, so the node (and its embedded `Links`) must not move until it
+/// has been unlinked again. `push_pinned`/`PinnedEntry` are the safe way to
+/// uphold that.
+pub struct Links<T> {
+    next: Rawlink<T>,
+    prev: Rawlink<T>,
+    inserted: bool,
+    _pin: PhantomPinned,
+}
+
+impl<T> Links<T> {
+    pub fn new() -> Links<T> {
+        Links {
+            next: Rawlink::none(),
+            prev: Rawlink::none(),
+            inserted: false,
+            _pin: PhantomPinned,
+        }
+    }
+}
+
+/// Decouples `IntrusiveList` from the type that owns the `Links` it
+/// manipulates.
+///
+/// An implementor embeds a `Links<Self::Target>` somewhere inside
+/// `Self::Target` and tells the list how to reach it, and how to convert
+/// between the handle pushed into (and popped out of) the list and the raw
+/// pointer the list stores while the value is a member.
+///
+/// # Safety
+///
+/// Implementors must guarantee:
+///
+/// - `as_raw` returns a pointer to the `Self::Target` that `handle` owns
+///   (or borrows, for pinned/borrowed handles), and that pointer stays
+///   valid and stable for as long as the value remains linked.
+/// - `from_raw` is the exact inverse of `as_raw`: calling it on a pointer
+///   produced by `as_raw` reconstructs a handle with the same ownership
+///   `as_raw` was given (so the list may later drop or otherwise consume
+///   it without violating whatever `Self::Handle` promises its owner).
+/// - `get_links` returns a pointer to a `Links<Self::Target>` that is
+///   actually embedded in `*target` and lives exactly as long as `*target`
+///   does, so the list may read and write through it for as long as the
+///   node stays linked.
+pub unsafe trait Link {
+    /// The handle given to, and returned from, the list, e.g. `Box<Target>`.
+    type Handle;
+
+    /// The type `Links<Self::Target>` is embedded in.
+    type Target;
+
+    /// Convert a handle into the raw pointer the list stores internally.
+    fn as_raw(handle: &Self::Handle) -> *mut Self::Target;
+
+    /// Convert a raw pointer taken out of the list back into a handle.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have come from `as_raw` on a handle that was pushed into
+    /// the list and has not already been converted back.
+    unsafe fn from_raw(ptr: *mut Self::Target) -> Self::Handle;
+
+    /// A raw pointer to the `Links` embedded in `*target`.
+    ///
+    /// This deliberately returns a raw pointer rather than a reference:
+    /// going through `&mut Links<Target>` would assert unique access to
+    /// (a field of) a node that other outstanding references — e.g. one
+    /// returned earlier by `LinkedList::get_mut` — may still be aliasing.
+    /// Callers write through the pointer directly instead of reifying a
+    /// reference (see `IntrusiveList::splice`/`unlink`).
+    ///
+    /// # Safety
+    ///
+    /// `target` must point at a live `Self::Target`.
+    unsafe fn get_links(target: *mut Self::Target) -> *mut Links<Self::Target>;
+}
+
+/// A move-stable identity for a list (`IntrusiveList` or `LinkedList`).
+///
+/// Nodes/entries keep a pointer to their owning list's `ListId` rather than
+/// to the list itself, so the identity survives the list being moved (e.g.
+/// the list `LinkedList::split_off` returns, which lives on the stack only
+/// until it is handed back to the caller). Boxing it gives it a heap
+/// address that does not change when the list holding it does; it must
+/// also not be a zero-sized type, or every `ListId` would share the same
+/// well-known dangling address and no longer be distinguishable.
+#[allow(dead_code)]
+struct ListId(u8);
+
+impl ListId {
+    fn new() -> ListId {
+        ListId(0)
+    }
+}
+
+/// An intrusive, doubly linked list.
+///
+/// Unlike `LinkedList<T>`, pushing never allocates: nodes are linked through
+/// a `Links<T>` field embedded in the caller's own type, so the same object
+/// can be a member of several `IntrusiveList`s simultaneously (one `Links`
+/// field per list) — the shape a readiness/wakeup queue in an event loop
+/// wants.
+///
+/// For the borrowed-node case (`push_pinned`), the list holds raw pointers
+/// into nodes it does not own, so it must be empty again before those
+/// borrows, or the `IntrusiveList` itself, go away.
+pub struct IntrusiveList<L: Link> {
+    head: Rawlink<L::Target>,
+    tail: Rawlink<L::Target>,
+    len: usize,
+    id: Box<ListId>,
+}
+
+impl<L: Link> IntrusiveList<L> {
+    pub fn new() -> IntrusiveList<L> {
+        IntrusiveList {
+            head: Rawlink::none(),
+            tail: Rawlink::none(),
+            len: 0,
+            id: Box::new(ListId::new()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Move every node of `other` onto the back of `self` in O(1), leaving
+    /// `other` empty.
+    pub fn append(&mut self, other: &mut IntrusiveList<L>) {
+        if let Some(other_head) = other.head.as_ptr() {
+            unsafe {
+                match self.tail.as_ptr() {
+                    Some(tail) => {
+                        (*L::get_links(tail)).next = other.head;
+                        (*L::get_links(other_head)).prev = self.tail;
+                    }
+                    None => self.head = other.head,
+                }
+            }
+
+            self.tail = other.tail;
+            self.len += other.len;
+
+            other.head = Rawlink::none();
+            other.tail = Rawlink::none();
+            other.len = 0;
+        }
+    }
+
+    /// Push `handle` onto the back of the list.
+    pub fn push(&mut self, handle: L::Handle) {
+        let raw = L::as_raw(&handle);
+        mem::forget(handle);
+
+        unsafe { self.link_tail(raw) };
+    }
+
+    /// Push a pinned, caller-owned node onto the back of the list without
+    /// allocating or taking ownership.
+    ///
+    /// The node's address is now relied on by the list for as long as it
+    /// stays linked, so the caller must not move it until it has been
+    /// unlinked again via `remove_pinned`.
+    pub fn push_pinned(&mut self, node: Pin<&mut L::Target>) -> PinnedEntry<L> {
+        // SAFETY: we only ever read `raw`'s `Links` field and never move out
+        // of the pointee; `Pin` guarantees its address stays stable for as
+        // long as it remains linked.
+        let raw = unsafe { Pin::get_unchecked_mut(node) } as *mut L::Target;
+
+        unsafe { self.link_tail(raw) };
+        PinnedEntry::new(self.id_ptr(), raw)
+    }
+
+    /// Remove `raw` from the list and hand back ownership of it.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must currently be a member of this list.
+    pub unsafe fn remove(&mut self, raw: *mut L::Target) -> L::Handle {
+        self.unlink(raw);
+        L::from_raw(raw)
+    }
+
+    /// Remove a node pushed with `push_pinned` from the list.
+    pub fn remove_pinned(&mut self, entry: PinnedEntry<L>) {
+        self.ensure_same_ll(&entry);
+        unsafe { self.unlink(entry.node) };
+        mem::forget(entry);
+    }
+
+    /// Splice `raw` into the list directly between `prev` and `next`
+    /// (either of which may be `Rawlink::none()` for the list's ends),
+    /// fixing up `head`/`tail` as needed.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must not already be linked; `prev`/`next`, if present, must
+    /// currently be this list's nodes on either side of where `raw` is
+    /// being inserted.
+    unsafe fn splice(&mut self, prev: Rawlink<L::Target>, next: Rawlink<L::Target>, raw: *mut L::Target) {
+        let links = L::get_links(raw);
+        assert!(!(*links).inserted, "node is already a member of a list");
+        (*links).inserted = true;
+        (*links).prev = prev;
+        (*links).next = next;
+
+        match prev.as_ptr() {
+            Some(prev) => (*L::get_links(prev)).next = Rawlink::some(raw),
+            None => self.head = Rawlink::some(raw),
+        }
+
+        match next.as_ptr() {
+            Some(next) => (*L::get_links(next)).prev = Rawlink::some(raw),
+            None => self.tail = Rawlink::some(raw),
+        }
+
+        self.len += 1;
+    }
+
+    unsafe fn link_tail(&mut self, raw: *mut L::Target) {
+        let tail = self.tail;
+        self.splice(tail, Rawlink::none(), raw);
+    }
+
+    unsafe fn unlink(&mut self, raw: *mut L::Target) {
+        let links = L::get_links(raw);
+        assert!((*links).inserted, "node is not a member of this list");
+        (*links).inserted = false;
+        let prev = (*links).prev;
+        let next = (*links).next;
+
+        match prev.as_ptr() {
+            Some(prev) => (*L::get_links(prev)).next = next,
+            None => self.head = next,
+        }
+
+        match next.as_ptr() {
+            Some(next) => (*L::get_links(next)).prev = prev,
+            None => self.tail = prev,
+        }
+
+        self.len -= 1;
+    }
+
+    fn id_ptr(&self) -> *mut ListId {
+        &*self.id as *const ListId as *mut ListId
+    }
+
+    fn ensure_same_ll(&self, entry: &PinnedEntry<L>) {
+        assert!(ptr::eq(entry.ll, self.id_ptr()), "entry belongs to a different IntrusiveList");
+    }
+}
+
+unsafe impl<L: Link> Send for IntrusiveList<L> {}
+
+/// A handle returned by `IntrusiveList::push_pinned`, standing in for the
+/// caller's pinned node while it remains linked.
+///
+/// Losing track of a `PinnedEntry` without unlinking its node first is a
+/// bug: the list still holds pointers into a node the caller may be about
+/// to move or drop. In debug builds, dropping a still-linked `PinnedEntry`
+/// panics instead of leaving those pointers dangling silently.
+pub struct PinnedEntry<L: Link> {
+    ll: *mut ListId,
+    node: *mut L::Target,
+}
+
+impl<L: Link> PinnedEntry<L> {
+    fn new(ll: *mut ListId, node: *mut L::Target) -> PinnedEntry<L> {
+        PinnedEntry { ll, node }
+    }
+}
+
+impl<L: Link> Drop for PinnedEntry<L> {
+    fn drop(&mut self) {
+        if cfg!(debug_assertions) {
+            let inserted = unsafe { (*L::get_links(self.node)).inserted };
+            assert!(!inserted, "PinnedEntry dropped while its node is still linked; call remove_pinned first");
+        }
+    }
+}
+
+unsafe impl<L: Link> Send for PinnedEntry<L> {}
+unsafe impl<L: Link> Sync for PinnedEntry<L> {}
 
+/// The owning, allocating list. Kept as the primary API; internally it is
+/// now just an `IntrusiveList` whose nodes happen to be heap allocated and
+/// owned by the list itself.
 pub struct LinkedList<T> {
-    head: Link<T>,
-    tail: Rawlink<Node<T>>,
+    list: IntrusiveList<NodeLink<T>>,
+    id: Box<ListId>,
+    /// Ids absorbed from lists merged in via `append`. A node whose `ll`
+    /// matches one of these is still recognized as belonging here, which
+    /// is what lets `append` move every node of `other` over without
+    /// visiting them to rewrite an owner pointer.
+    absorbed_ids: Vec<Box<ListId>>,
 }
 
 impl<T: Send + Sync> LinkedList<T> {
     pub fn new() -> LinkedList<T> {
         LinkedList {
-            head: None,
-            tail: Rawlink::none(),
+            list: IntrusiveList::new(),
+            id: Box::new(ListId::new()),
+            absorbed_ids: Vec::new(),
         }
     }
 }
 
 impl<T> LinkedList<T> {
     pub fn is_empty(&self) -> bool {
-        self.head.is_none()
+        self.list.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
     }
 
     pub fn get_mut(&mut self, entry: &Entry<T>) -> &mut T {
@@ -27,157 +343,369 @@ impl<T> LinkedList<T> {
         unsafe {
             // Save because &mut self has mutable access to the entire
             // LinkedList
-            let n: Option<&mut Node<T>> = mem::transmute(entry.node);
-            &mut n.unwrap().value
+            &mut (*entry.node).value
         }
     }
 
     pub fn push(&mut self, el: T) -> Entry<T> {
-        let mut node = Box::new(Node::new(el));
-        let entry = Entry::new(self, &mut node);
-
-        match unsafe { self.tail.resolve_mut() } {
-            None => self.push_front(node),
-            Some(tail) => {
-                tail.set_next(node);
-                self.tail = Rawlink::from(&mut tail.next);
-            }
-        }
-
-        entry
+        let ll = self.id_ptr();
+        let node = Box::new(Node {
+            links: Links::new(),
+            value: el,
+            ll,
+            _pin: PhantomPinned,
+        });
+        let raw = &*node as *const Node<T> as *mut Node<T>;
+
+        self.list.push(node);
+        Entry::new(raw)
     }
 
-    pub fn remove(&mut self, mut entry: Entry<T>) -> T {
+    pub fn remove(&mut self, entry: Entry<T>) -> T {
         self.ensure_same_ll(&entry);
 
-        let (mut prev, next) = unsafe {
-            let node = entry.node.resolve_mut().expect("invalid entry");
-            (node.prev, node.next.take())
-        };
+        let node = unsafe { self.list.remove(entry.node) };
+        node.value
+    }
 
-        // Unlink previous pointer
-        let removed = match unsafe { (prev.resolve_mut(), next) } {
-            (Some(p), Some(mut next)) => {
-                next.prev = prev;
-                *mem::replace(&mut p.next, Some(next)).take().unwrap()
-            }
-            (None, Some(mut next)) => {
-                next.prev = Rawlink::none();
-                *mem::replace(&mut self.head, Some(next)).take().unwrap()
-            }
-            (Some(p), None) => {
-                self.tail = prev;
-                *p.next.take().unwrap()
-            }
-            (None, None) => {
-                self.tail = Rawlink::none();
-                *self.head.take().unwrap()
-            }
-        };
+    pub fn iter(&self) -> Iter<T> {
+        Iter {
+            head: self.list.head,
+            tail: self.list.tail,
+            len: self.list.len(),
+            _marker: PhantomData,
+        }
+    }
 
-        debug_assert!(removed.next.is_none());
-        removed.value
+    /// An iterator over references to the items of the list, back to front.
+    ///
+    /// Equivalent to `self.iter().rev()`, spelled out as its own method
+    /// since walking a doubly linked list backwards from `tail` is just as
+    /// natural as walking it forwards and callers shouldn't have to know
+    /// `Iter` implements `DoubleEndedIterator` to find it.
+    pub fn iter_rev(&self) -> Rev<Iter<T>> {
+        self.iter().rev()
     }
 
-    pub fn iter(&self) -> Iter<T> {
-        Iter { curr: &self.head }
+    /// A cursor starting at the front of the list, for splicing nodes in
+    /// relative to a walked position.
+    pub fn cursor_mut(&mut self) -> CursorMut<T> {
+        CursorMut {
+            curr: self.list.head,
+            list: self,
+        }
+    }
+
+    /// Move every element of `other` onto the back of `self` in O(1),
+    /// leaving `other` empty.
+    ///
+    /// The migrated nodes' `ll` pointers are left untouched; instead
+    /// `self` absorbs `other`'s id (and anything `other` had itself
+    /// already absorbed), so `ensure_same_ll` still recognizes them as
+    /// belonging to `self` without a per-node rewrite.
+    pub fn append(&mut self, other: &mut LinkedList<T>) {
+        let other_id = mem::replace(&mut other.id, Box::new(ListId::new()));
+        self.absorbed_ids.push(other_id);
+        self.absorbed_ids.append(&mut other.absorbed_ids);
+
+        self.list.append(&mut other.list);
     }
 
-    fn push_front(&mut self, mut new_head: Box<Node<T>>) {
-        match self.head {
-            None => {
-                self.head = link_no_prev(new_head);
-                self.tail = Rawlink::from(&mut self.head);
+    /// Split the list in two at `entry`: `self` keeps every element before
+    /// it, and the elements from `entry` onward (inclusive) move into the
+    /// returned list.
+    ///
+    /// Every migrated node's `ll` is rewritten to point at the returned
+    /// list's (boxed, move-stable) id, so `Entry`s created for it before
+    /// the split still see the right list afterwards — including once
+    /// `new_list` itself has been moved out by returning it.
+    pub fn split_off(&mut self, entry: &Entry<T>) -> LinkedList<T>
+    where
+        T: Send + Sync,
+    {
+        self.ensure_same_ll(entry);
+
+        let mut new_list = LinkedList::new();
+        let at = entry.node;
+
+        unsafe {
+            let prev = (*at).links.prev;
+
+            match prev.as_ptr() {
+                Some(p) => (*p).links.next = Rawlink::none(),
+                None => self.list.head = Rawlink::none(),
             }
-            Some(ref mut head) => {
-                new_head.prev = Rawlink::none();
-                head.prev = Rawlink::some(&mut *new_head);
-                mem::swap(head, &mut new_head);
-                head.next = Some(new_head);
+            (*at).links.prev = Rawlink::none();
+
+            new_list.list.head = Rawlink::some(at);
+            new_list.list.tail = self.list.tail;
+            self.list.tail = prev;
+
+            let new_ll = new_list.id_ptr();
+            let mut moved = 0;
+            let mut cur = Rawlink::some(at);
+            while let Some(raw) = cur.as_ptr() {
+                (*raw).ll = new_ll;
+                moved += 1;
+                cur = (*raw).links.next;
             }
+
+            new_list.list.len = moved;
+            self.list.len -= moved;
         }
+
+        new_list
+    }
+
+    fn id_ptr(&self) -> *mut ListId {
+        &*self.id as *const ListId as *mut ListId
     }
 
     fn ensure_same_ll(&self, entry: &Entry<T>) {
-        assert!(entry.ll == self as *const LinkedList<T> as *mut LinkedList<T>, "entry belongs to a different LinkedList");
+        let owner = unsafe { (*entry.node).ll };
+        let owns = ptr::eq(owner, self.id_ptr())
+            || self
+                .absorbed_ids
+                .iter()
+                .any(|id| ptr::eq(owner, &**id as *const ListId as *mut ListId));
+        assert!(owns, "entry belongs to a different LinkedList");
     }
 }
 
 unsafe impl<T> Send for LinkedList<T> {}
 
+impl<T> Drop for LinkedList<T> {
+    /// Frees every remaining node iteratively.
+    ///
+    /// `Node<T>` only owns its `Links`' raw pointers to its neighbours, not
+    /// the neighbours themselves, so this does not recurse — it simply
+    /// walks `head` to `tail` converting each raw pointer back into the
+    /// `Box<Node<T>>` it was allocated as and dropping it, freeing arbitrarily
+    /// long lists in constant stack space.
+    fn drop(&mut self) {
+        unsafe {
+            let mut cur = self.list.head;
+            while let Some(raw) = cur.as_ptr() {
+                cur = (*raw).links.next;
+                drop(NodeLink::<T>::from_raw(raw));
+            }
+        }
+    }
+}
+
 pub struct Entry<T> {
-    ll: *mut LinkedList<T>,
-    node: Rawlink<Node<T>>,
+    node: *mut Node<T>,
 }
 
 impl<T> Entry<T> {
-    fn new(ll: &mut LinkedList<T>, node: &mut Node<T>) -> Entry<T> {
-        Entry {
-            ll: ll as *mut LinkedList<T>,
-            node: Rawlink::some(node),
+    fn new(node: *mut Node<T>) -> Entry<T> {
+        Entry { node }
+    }
+}
+
+/// A cursor that walks a `LinkedList`'s nodes and can splice new ones in,
+/// or remove the current one, in place.
+///
+/// `peek_next`/`peek_prev` and the `insert_*` methods read and write
+/// through raw pointers into neighboring nodes rather than ever taking a
+/// `&mut` to the whole current node, so a reference previously returned by
+/// `current()` stays valid across them.
+pub struct CursorMut<'a, T> {
+    list: &'a mut LinkedList<T>,
+    curr: Rawlink<Node<T>>,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// The element at the cursor's position, or `None` if the cursor has
+    /// run off the end of the list.
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.curr.as_ptr().map(|raw| unsafe { &mut (*raw).value })
+    }
+
+    /// Move to the next node.
+    ///
+    /// Having run off one end of the list is a single "ghost" position
+    /// shared by both directions, not a dead end: from there, `move_next`
+    /// re-enters at the front.
+    pub fn move_next(&mut self) {
+        self.curr = match self.curr.as_ptr() {
+            Some(raw) => unsafe { (*raw).links.next },
+            None => self.list.list.head,
+        };
+    }
+
+    /// Move to the previous node.
+    ///
+    /// Symmetric to `move_next`: from the ghost position past either end,
+    /// `move_prev` re-enters at the back.
+    pub fn move_prev(&mut self) {
+        self.curr = match self.curr.as_ptr() {
+            Some(raw) => unsafe { (*raw).links.prev },
+            None => self.list.list.tail,
+        };
+    }
+
+    /// Look at the element after the cursor without moving to it.
+    pub fn peek_next(&self) -> Option<&T> {
+        let raw = self.curr.as_ptr()?;
+        unsafe { (*raw).links.next.as_ptr().map(|n| &(*n).value) }
+    }
+
+    /// Look at the element before the cursor without moving to it.
+    pub fn peek_prev(&self) -> Option<&T> {
+        let raw = self.curr.as_ptr()?;
+        unsafe { (*raw).links.prev.as_ptr().map(|p| &(*p).value) }
+    }
+
+    /// Insert `el` right after the cursor's position (at the back of the
+    /// list if the cursor has run off the end).
+    pub fn insert_after(&mut self, el: T) -> Entry<T> {
+        let raw = new_node(self.list.id_ptr(), el);
+
+        unsafe {
+            match self.curr.as_ptr() {
+                Some(cur) => {
+                    let next = (*cur).links.next;
+                    self.list.list.splice(Rawlink::some(cur), next, raw);
+                }
+                None => {
+                    let tail = self.list.list.tail;
+                    self.list.list.splice(tail, Rawlink::none(), raw);
+                }
+            }
+        }
+
+        Entry::new(raw)
+    }
+
+    /// Insert `el` right before the cursor's position (at the front of the
+    /// list if the cursor has run off the end).
+    pub fn insert_before(&mut self, el: T) -> Entry<T> {
+        let raw = new_node(self.list.id_ptr(), el);
+
+        unsafe {
+            match self.curr.as_ptr() {
+                Some(cur) => {
+                    let prev = (*cur).links.prev;
+                    self.list.list.splice(prev, Rawlink::some(cur), raw);
+                }
+                None => {
+                    let head = self.list.list.head;
+                    self.list.list.splice(Rawlink::none(), head, raw);
+                }
+            }
         }
+
+        Entry::new(raw)
+    }
+
+    /// Remove the element at the cursor, moving the cursor to what was the
+    /// next element.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let raw = self.curr.as_ptr()?;
+        self.curr = unsafe { (*raw).links.next };
+
+        let entry = Entry::new(raw);
+        Some(self.list.remove(entry))
     }
 }
 
+/// Allocate a boxed node for `el` owned by the list at `ll`, and hand back
+/// its stable address, transferring ownership to whoever links it in
+/// (mirrors what `IntrusiveList::push` does for a `Box<Node<T>>` handle).
+fn new_node<T>(ll: *mut ListId, el: T) -> *mut Node<T> {
+    let node = Box::new(Node {
+        links: Links::new(),
+        value: el,
+        ll,
+        _pin: PhantomPinned,
+    });
+    let raw = &*node as *const Node<T> as *mut Node<T>;
+    mem::forget(node);
+    raw
+}
+
 /// An iterator over references to the items of a `LinkedList`.
-pub struct Iter<'a, T:'a> {
-    curr: &'a Link<T>,
+///
+/// Tracks both ends (`head`/`tail`) plus a remaining `len` so `next` and
+/// `next_back` can be driven independently and still agree on when the
+/// iterator is exhausted, rather than yielding the same node from both
+/// directions.
+pub struct Iter<'a, T: 'a> {
+    head: Rawlink<Node<T>>,
+    tail: Rawlink<Node<T>>,
+    len: usize,
+    _marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        unsafe {
+            self.head.resolve_ref().map(|node| {
+                self.len -= 1;
+                self.head = node.links.next;
+                &node.value
+            })
+        }
+    }
 }
 
-impl<'a, A> Iterator for Iter<'a, A> {
-    type Item = &'a A;
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
 
-    fn next(&mut self) -> Option<&'a A> {
-        self.curr.as_ref().map(|curr| {
-            self.curr = &curr.next;
-            &curr.value
-        })
+        unsafe {
+            self.tail.resolve_ref().map(|node| {
+                self.len -= 1;
+                self.tail = node.links.prev;
+                &node.value
+            })
+        }
     }
 }
 
 unsafe impl<T> Send for Entry<T> {}
 unsafe impl<T> Sync for Entry<T> {}
 
-type Link<T> = Option<Box<Node<T>>>;
-
-/// Clear the .prev field on `next`, then return `Some(next)`
-fn link_no_prev<T>(mut next: Box<Node<T>>) -> Link<T> {
-    next.prev = Rawlink::none();
-    Some(next)
-}
-
 struct Rawlink<T> {
-    p: *mut T,
+    p: Option<NonNull<T>>,
 }
 
 impl<T> Rawlink<T> {
     /// Like Option::None for Rawlink
     fn none() -> Rawlink<T> {
-        Rawlink{p: ptr::null_mut()}
+        Rawlink { p: None }
     }
 
     /// Like Option::Some for Rawlink
-    fn some(n: &mut T) -> Rawlink<T> {
-        Rawlink{p: n}
+    fn some(n: *mut T) -> Rawlink<T> {
+        Rawlink { p: NonNull::new(n) }
     }
 
-    /// Convert the `Rawlink` into an Option value
-    ///
-    /// **unsafe** because:
+    /// The raw pointer behind this link, if any.
     ///
-    /// - Dereference of raw pointer.
-    /// - Returns reference of arbitrary lifetime.
-    unsafe fn resolve_mut<'a>(&mut self) -> Option<&'a mut T> {
-        mem::transmute(self.p)
+    /// Deliberately not a reference: callers write through the pointer
+    /// directly rather than materializing a `&mut` that could invalidate
+    /// references already handed out into the same node.
+    fn as_ptr(&self) -> Option<*mut T> {
+        self.p.map(NonNull::as_ptr)
     }
-}
 
-impl<'a, T> From<&'a mut Link<T>> for Rawlink<Node<T>> {
-    fn from(node: &'a mut Link<T>) -> Self {
-        match node.as_mut() {
-            None => Rawlink::none(),
-            Some(ptr) => Rawlink::some(ptr),
-        }
+    /// Borrow the pointee immutably, for read-only traversal (e.g. `Iter`).
+    ///
+    /// # Safety
+    ///
+    /// The pointee must be live and not currently borrowed mutably.
+    unsafe fn resolve_ref<'a>(&self) -> Option<&'a T> {
+        self.p.map(|n| &*n.as_ptr())
     }
 }
 
@@ -190,30 +718,224 @@ impl<T> Clone for Rawlink<T> {
 }
 
 struct Node<T> {
-    next: Link<T>,
-    prev: Rawlink<Node<T>>,
+    links: Links<Node<T>>,
     value: T,
+    /// The id of the `LinkedList` this node was created in or last moved
+    /// to by `split_off`, kept on the node itself (not on `Entry`) so
+    /// `split_off` can repoint it when a node migrates, keeping every
+    /// `Entry` issued for it — past or future — valid against its new
+    /// owner. `append` leaves it as it is; the receiving list absorbs the
+    /// old id instead. Points at a `ListId`, not the `LinkedList` itself,
+    /// since the former has a stable address even when the latter moves.
+    ll: *mut ListId,
+    _pin: PhantomPinned,
 }
 
-impl<T> Node<T> {
-    fn new(v: T) -> Node<T> {
-        Node {
-            value: v,
-            next: None,
-            prev: Rawlink::none(),
-        }
+/// Ties `LinkedList<T>`'s owned, boxed nodes into `IntrusiveList` by
+/// implementing `Link` for them: the list's `Handle` is the `Box<Node<T>>`
+/// the node is allocated as, and the node embeds its own `Links`.
+struct NodeLink<T>(PhantomData<T>);
+
+unsafe impl<T> Link for NodeLink<T> {
+    type Handle = Box<Node<T>>;
+    type Target = Node<T>;
+
+    fn as_raw(handle: &Box<Node<T>>) -> *mut Node<T> {
+        &**handle as *const Node<T> as *mut Node<T>
     }
 
-    /// Update the `prev` link on `next`, then set self's next pointer.
-    ///
-    /// `self.next` should be `None` when you call this
-    /// (otherwise a Node is probably being dropped by mistake).
-    fn set_next(&mut self, mut next: Box<Node<T>>) {
-        debug_assert!(self.next.is_none());
-        next.prev = Rawlink::some(self);
-        self.next = Some(next);
+    unsafe fn from_raw(ptr: *mut Node<T>) -> Box<Node<T>> {
+        Box::from_raw(ptr)
+    }
+
+    unsafe fn get_links(target: *mut Node<T>) -> *mut Links<Node<T>> {
+        ptr::addr_of_mut!((*target).links)
     }
 }
 
 unsafe impl<T> Send for Node<T> {}
 unsafe impl<T> Sync for Node<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_get_mut_remove() {
+        let mut list = LinkedList::new();
+        let a = list.push(1);
+        let b = list.push(2);
+        assert_eq!(list.len(), 2);
+
+        *list.get_mut(&a) += 10;
+        assert_eq!(list.remove(a), 11);
+        assert_eq!(list.remove(b), 2);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn iter_forward_and_rev() {
+        let mut list = LinkedList::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(list.iter_rev().copied().collect::<Vec<_>>(), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn double_ended_iterator_meets_in_the_middle() {
+        let mut list = LinkedList::new();
+        for i in 1..=5 {
+            list.push(i);
+        }
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn cursor_insert_and_remove() {
+        let mut list = LinkedList::new();
+        list.push(2);
+
+        {
+            let mut cursor = list.cursor_mut();
+            cursor.insert_before(1);
+            // Cursor is still at the front-most pre-existing node.
+            assert_eq!(cursor.current(), Some(&mut 2));
+            cursor.insert_after(3);
+            assert_eq!(cursor.peek_next(), Some(&3));
+            assert_eq!(cursor.peek_prev(), Some(&1));
+        }
+
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn cursor_reenters_from_the_ghost_position() {
+        let mut list = LinkedList::new();
+        list.push(1);
+        list.push(2);
+
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+
+        // Past the tail is the same ghost position as past the head: moving
+        // forward from it re-enters at the front, backward re-enters at the
+        // back.
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 2));
+
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 2));
+    }
+
+    #[test]
+    fn append_moves_other_in_and_existing_entries_still_work() {
+        let mut a = LinkedList::new();
+        let mut b = LinkedList::new();
+        a.push(1);
+        let entry = b.push(2);
+
+        a.append(&mut b);
+
+        assert!(b.is_empty());
+        assert_eq!(a.iter().copied().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(*a.get_mut(&entry), 2);
+    }
+
+    #[test]
+    fn split_off_keeps_entries_valid_even_after_the_new_list_is_moved() {
+        let mut list = LinkedList::new();
+        list.push(1);
+        let at = list.push(2);
+        list.push(3);
+
+        let tail = list.split_off(&at);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(tail.iter().copied().collect::<Vec<_>>(), vec![2, 3]);
+
+        // Relocate `tail` (e.g. as `split_off` itself must, returning it to
+        // its caller) before using the `Entry` created for it beforehand.
+        let mut parked = Vec::new();
+        parked.push(tail);
+        assert_eq!(*parked[0].get_mut(&at), 2);
+    }
+
+    struct TestNode {
+        links: Links<TestNode>,
+        value: i32,
+    }
+
+    struct TestLink;
+
+    unsafe impl Link for TestLink {
+        type Handle = Box<TestNode>;
+        type Target = TestNode;
+
+        fn as_raw(handle: &Box<TestNode>) -> *mut TestNode {
+            &**handle as *const TestNode as *mut TestNode
+        }
+
+        unsafe fn from_raw(ptr: *mut TestNode) -> Box<TestNode> {
+            Box::from_raw(ptr)
+        }
+
+        unsafe fn get_links(target: *mut TestNode) -> *mut Links<TestNode> {
+            ptr::addr_of_mut!((*target).links)
+        }
+    }
+
+    #[test]
+    fn push_pinned_and_remove_pinned() {
+        let mut list = IntrusiveList::<TestLink>::new();
+        let mut node = TestNode {
+            links: Links::new(),
+            value: 42,
+        };
+        let entry = list.push_pinned(unsafe { Pin::new_unchecked(&mut node) });
+        assert_eq!(list.len(), 1);
+
+        list.remove_pinned(entry);
+        assert!(list.is_empty());
+        assert_eq!(node.value, 42);
+    }
+
+    fn build_list_with(node: Pin<&mut TestNode>) -> (IntrusiveList<TestLink>, PinnedEntry<TestLink>) {
+        let mut list = IntrusiveList::new();
+        let entry = list.push_pinned(node);
+        (list, entry)
+    }
+
+    #[test]
+    fn intrusive_list_survives_being_moved_after_push_pinned() {
+        let mut node = TestNode {
+            links: Links::new(),
+            value: 7,
+        };
+        let (mut list, entry) = build_list_with(unsafe { Pin::new_unchecked(&mut node) });
+
+        // `list` was returned by value (moved) from `build_list_with` after
+        // `entry` was created against it; `remove_pinned` must still
+        // recognize `entry` as belonging to it.
+        list.remove_pinned(entry);
+        assert!(list.is_empty());
+    }
+}